@@ -1,14 +1,22 @@
 mod raw_context_wrapper;
 use self::raw_context_wrapper::RawContextWrapper;
 
-use std::{mem::MaybeUninit, sync::Arc};
+use std::{mem::MaybeUninit, sync::Arc, time::Duration};
 
 use libc::{c_char, c_int};
+#[cfg(unix)]
+use libc::{c_short, POLLIN, POLLOUT};
 use libusb::*;
 
+use device::Device;
 use device_handle::{self, DeviceHandle};
 use device_list::{self, DeviceList};
 use error;
+use hotplug::{self, HotplugBuilder, HotplugEvent, Registration};
+#[cfg(unix)]
+use std::os::raw::c_void;
+#[cfg(unix)]
+use std::os::unix::io::RawFd;
 use std::{ffi::CStr, sync::Mutex};
 
 /// A `libusb` context.
@@ -44,9 +52,59 @@ impl LogCallbackMap {
     }
 }
 
+#[cfg(unix)]
+type PollfdAddedCallback = Box<dyn Fn(RawFd, PollEvents)>;
+#[cfg(unix)]
+type PollfdRemovedCallback = Box<dyn Fn(RawFd)>;
+
+#[cfg(unix)]
+struct PollfdNotifierMap {
+    map: std::collections::HashMap<*mut libusb_context, (PollfdAddedCallback, PollfdRemovedCallback)>,
+}
+
+#[cfg(unix)]
+unsafe impl Sync for PollfdNotifierMap {}
+#[cfg(unix)]
+unsafe impl Send for PollfdNotifierMap {}
+
+#[cfg(unix)]
+impl PollfdNotifierMap {
+    pub fn new() -> Self {
+        Self {
+            map: std::collections::HashMap::new(),
+        }
+    }
+}
+
 lazy_static::lazy_static! {
     static ref LOG_CALLBACK_MAP: Mutex<LogCallbackMap> = Mutex::new(LogCallbackMap::new());
-    static ref DEFAULT_CONTEXT_INITIALIZED_FLAG: Mutex<bool> = Mutex::new(false);
+}
+
+#[cfg(unix)]
+lazy_static::lazy_static! {
+    static ref POLLFD_NOTIFIER_MAP: Mutex<PollfdNotifierMap> = Mutex::new(PollfdNotifierMap::new());
+}
+
+#[cfg(unix)]
+extern "C" fn static_pollfd_added_callback(fd: c_int, events: c_short, user_data: *mut c_void) {
+    let ctx = user_data as *mut libusb_context;
+
+    if let Ok(locked_table) = POLLFD_NOTIFIER_MAP.lock() {
+        if let Some((added, _)) = locked_table.map.get(&ctx) {
+            added(fd as RawFd, PollEvents(events));
+        }
+    }
+}
+
+#[cfg(unix)]
+extern "C" fn static_pollfd_removed_callback(fd: c_int, user_data: *mut c_void) {
+    let ctx = user_data as *mut libusb_context;
+
+    if let Ok(locked_table) = POLLFD_NOTIFIER_MAP.lock() {
+        if let Some((_, removed)) = locked_table.map.get(&ctx) {
+            removed(fd as RawFd);
+        }
+    }
 }
 
 extern "C" fn static_log_callback(context: *mut libusb_context, level: c_int, text: *const c_char) {
@@ -71,33 +129,8 @@ impl Context {
         })
     }
 
-    pub fn init_default_context() -> ::Result<()> {
-        if let Ok(mut flag) = DEFAULT_CONTEXT_INITIALIZED_FLAG.lock() {
-            if !*flag {
-                try_unsafe!(libusb_init(std::ptr::null_mut()));
-                *flag = true;
-            }
-        }
-        Ok(())
-    }
-
-    pub fn release_default_context() {
-        if let Ok(mut flag) = DEFAULT_CONTEXT_INITIALIZED_FLAG.lock() {
-            if *flag {
-                unsafe { libusb_exit(std::ptr::null_mut()) };
-                *flag = false;
-            }
-        }
-    }
-
-    /// Sets the log level of a `libusb` context.
-    pub fn set_log_level(&mut self, level: LogLevel) {
-        unsafe {
-            libusb_set_option(**self.context, LIBUSB_OPTION_LOG_LEVEL, level.as_c_int());
-        }
-    }
-
-    /// Sets the log level for the default context.
+    /// Sets the log level for the default context, without requiring an instance of
+    /// [`GlobalContext`](struct.GlobalContext.html).
     pub fn set_default_context_log_level(level: LogLevel) {
         unsafe {
             libusb_set_option(
@@ -122,31 +155,72 @@ impl Context {
             libusb_set_log_cb(**self.context, static_log_callback, mode.as_c_int());
         }
     }
+}
+
+impl Drop for Context {
+    fn drop(&mut self) {
+        // Clones share the raw context; only the last one unregisters its callbacks.
+        if Arc::strong_count(&self.context) > 1 {
+            return;
+        }
+
+        if let Ok(mut locked_table) = LOG_CALLBACK_MAP.lock() {
+            locked_table.map.remove(&**self.context);
+        }
+
+        #[cfg(unix)]
+        {
+            if let Ok(mut locked_table) = POLLFD_NOTIFIER_MAP.lock() {
+                locked_table.map.remove(&**self.context);
+            }
+        }
+    }
+}
+
+impl UsbContext for Context {
+    fn as_raw(&self) -> *mut libusb_context {
+        **self.context
+    }
+}
+
+/// Common surface shared by an owned [`Context`](struct.Context.html) and the zero-sized
+/// [`GlobalContext`](struct.GlobalContext.html), so `Device`, `DeviceHandle`, and friends work the
+/// same way against either.
+pub trait UsbContext: Clone + Sized + Send + Sync + 'static {
+    /// Returns the raw `libusb_context` pointer, or `null_mut()` for the default context.
+    fn as_raw(&self) -> *mut libusb_context;
+
+    /// Sets the log level of this context.
+    fn set_log_level(&mut self, level: LogLevel) {
+        unsafe {
+            libusb_set_option(self.as_raw(), LIBUSB_OPTION_LOG_LEVEL, level.as_c_int());
+        }
+    }
 
-    pub fn has_capability(&self) -> bool {
+    fn has_capability(&self) -> bool {
         unsafe { libusb_has_capability(LIBUSB_CAP_HAS_CAPABILITY) != 0 }
     }
 
     /// Tests whether the running `libusb` library supports hotplug.
-    pub fn has_hotplug(&self) -> bool {
+    fn has_hotplug(&self) -> bool {
         unsafe { libusb_has_capability(LIBUSB_CAP_HAS_HOTPLUG) != 0 }
     }
 
     /// Tests whether the running `libusb` library has HID access.
-    pub fn has_hid_access(&self) -> bool {
+    fn has_hid_access(&self) -> bool {
         unsafe { libusb_has_capability(LIBUSB_CAP_HAS_HID_ACCESS) != 0 }
     }
 
     /// Tests whether the running `libusb` library supports detaching the kernel driver.
-    pub fn supports_detach_kernel_driver(&self) -> bool {
+    fn supports_detach_kernel_driver(&self) -> bool {
         unsafe { libusb_has_capability(LIBUSB_CAP_SUPPORTS_DETACH_KERNEL_DRIVER) != 0 }
     }
 
     /// Returns a list of the current USB devices. The context must outlive the device list.
-    pub fn devices(&self) -> ::Result<DeviceList> {
+    fn devices(&self) -> ::Result<DeviceList<Self>> {
         let mut list: *const *mut libusb_device = unsafe { MaybeUninit::uninit().assume_init() };
 
-        let n = unsafe { libusb_get_device_list(**self.context, &mut list) };
+        let n = unsafe { libusb_get_device_list(self.as_raw(), &mut list) };
 
         if n < 0 {
             Err(error::from_libusb(n as c_int))
@@ -163,13 +237,13 @@ impl Context {
     ///
     /// Returns a device handle for the first device found matching `vendor_id` and `product_id`.
     /// On error, or if the device could not be found, it returns `None`.
-    pub fn open_device_with_vid_pid(
+    fn open_device_with_vid_pid(
         &self,
         vendor_id: u16,
         product_id: u16,
-    ) -> Option<DeviceHandle> {
+    ) -> Option<DeviceHandle<Self>> {
         let handle =
-            unsafe { libusb_open_device_with_vid_pid(**self.context, vendor_id, product_id) };
+            unsafe { libusb_open_device_with_vid_pid(self.as_raw(), vendor_id, product_id) };
 
         if handle.is_null() {
             None
@@ -177,16 +251,214 @@ impl Context {
             Some(unsafe { device_handle::from_libusb(self.clone(), handle) })
         }
     }
+
+    /// Registers a callback that is invoked when devices matching `builder`'s filters are
+    /// plugged in or removed.
+    ///
+    /// Requires [`has_hotplug`](#method.has_hotplug) to return `true`. The returned
+    /// [`Registration`](../hotplug/struct.Registration.html) deregisters the callback when
+    /// dropped. Callbacks only fire while this context's events are being pumped, e.g. via
+    /// [`handle_events`](#method.handle_events).
+    fn register_hotplug_callback(
+        &self,
+        builder: &HotplugBuilder,
+        callback: Box<dyn FnMut(Device<Self>, HotplugEvent)>,
+    ) -> ::Result<Registration<Self>> {
+        hotplug::register_callback(self, builder, callback)
+    }
+
+    /// Handles any pending events, blocking until at least one event is handled.
+    ///
+    /// This is one way to keep hotplug and transfer callbacks firing; see
+    /// [`handle_events_timeout`](#method.handle_events_timeout) for a variant with a bounded wait.
+    fn handle_events(&self) -> ::Result<()> {
+        try_unsafe!(libusb_handle_events(self.as_raw()));
+        Ok(())
+    }
+
+    /// Handles any pending events, blocking for at most `timeout`.
+    fn handle_events_timeout(&self, timeout: Duration) -> ::Result<()> {
+        let tv = libc::timeval {
+            tv_sec: timeout.as_secs() as libc::time_t,
+            tv_usec: libc::suseconds_t::from(timeout.subsec_micros() as i32),
+        };
+
+        let mut completed: c_int = 0;
+        try_unsafe!(libusb_handle_events_timeout_completed(
+            self.as_raw(),
+            &tv,
+            &mut completed
+        ));
+        Ok(())
+    }
+
+    /// Returns how long the caller may wait before the next call to `handle_events` needs to
+    /// run, or `None` if `libusb` has no pending timeout.
+    fn next_timeout(&self) -> Option<Duration> {
+        let mut tv: libc::timeval = unsafe { MaybeUninit::uninit().assume_init() };
+        let rc = unsafe { libusb_get_next_timeout(self.as_raw(), &mut tv) };
+
+        if rc == 1 {
+            Some(Duration::new(tv.tv_sec as u64, (tv.tv_usec as u32) * 1_000))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the file descriptors that `libusb` currently wants polled for readiness, for
+    /// integrating this context with `poll`/`epoll` or an async executor.
+    #[cfg(unix)]
+    fn pollfds(&self) -> Vec<(RawFd, PollEvents)> {
+        let list = unsafe { libusb_get_pollfds(self.as_raw()) };
+
+        if list.is_null() {
+            return Vec::new();
+        }
+
+        let mut fds = Vec::new();
+
+        unsafe {
+            let mut i = 0;
+
+            loop {
+                let entry = *list.offset(i);
+
+                if entry.is_null() {
+                    break;
+                }
+
+                fds.push(((*entry).fd as RawFd, PollEvents((*entry).events)));
+                i += 1;
+            }
+
+            libusb_free_pollfds(list);
+        }
+
+        fds
+    }
+
+    /// Wraps an already-open OS file descriptor for a USB device.
+    #[cfg(unix)]
+    fn wrap_sys_device(&self, fd: RawFd) -> ::Result<DeviceHandle<Self>> {
+        let mut handle: *mut libusb_device_handle = unsafe { MaybeUninit::uninit().assume_init() };
+
+        try_unsafe!(libusb_wrap_sys_device(
+            self.as_raw(),
+            fd as libc::intptr_t,
+            &mut handle
+        ));
+
+        Ok(unsafe { device_handle::from_libusb(self.clone(), handle) })
+    }
+
+    /// Registers callbacks invoked whenever `libusb` adds or removes a pollfd from the set
+    /// returned by [`pollfds`](#method.pollfds).
+    #[cfg(unix)]
+    fn set_pollfd_notifiers(
+        &self,
+        added: Box<dyn Fn(RawFd, PollEvents)>,
+        removed: Box<dyn Fn(RawFd)>,
+    ) {
+        let ctx = self.as_raw();
+
+        if let Ok(mut locked_table) = POLLFD_NOTIFIER_MAP.lock() {
+            locked_table.map.insert(ctx, (added, removed));
+        }
+
+        unsafe {
+            libusb_set_pollfd_notifiers(
+                ctx,
+                static_pollfd_added_callback,
+                static_pollfd_removed_callback,
+                ctx as *mut c_void,
+            );
+        }
+    }
 }
 
-impl Drop for Context {
+lazy_static::lazy_static! {
+    static ref GLOBAL_CONTEXT_REFCOUNT: Mutex<usize> = Mutex::new(0);
+}
+
+/// A zero-sized handle to `libusb`'s default context.
+///
+/// Unlike an owned [`Context`](struct.Context.html), `GlobalContext` doesn't allocate its own
+/// `libusb_context`; it instead holds a ref-counted handle to `libusb`'s process-wide default
+/// context (the one `NULL` refers to in the C API), which `libusb_init`/`libusb_exit` require be
+/// explicitly initialized and torn down. The first `GlobalContext` created in the process
+/// initializes it; the default context is released once the last one is dropped. This is
+/// convenient for prototypes, or for code that wants to talk to USB devices without threading an
+/// owned [`Context`](struct.Context.html) through its API. Prefer an owned `Context` when isolated
+/// logging, independent hotplug registrations, or deterministic shutdown matter.
+#[derive(Debug)]
+pub struct GlobalContext(());
+
+unsafe impl Sync for GlobalContext {}
+unsafe impl Send for GlobalContext {}
+
+impl GlobalContext {
+    /// Acquires a handle to `libusb`'s default context, initializing it if none of the process's
+    /// other `GlobalContext`s are currently alive.
+    pub fn new() -> Self {
+        let mut count = GLOBAL_CONTEXT_REFCOUNT.lock().unwrap();
+
+        if *count == 0 {
+            unsafe { libusb_init(std::ptr::null_mut()) };
+        }
+
+        *count += 1;
+        GlobalContext(())
+    }
+}
+
+impl Default for GlobalContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clone for GlobalContext {
+    fn clone(&self) -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for GlobalContext {
     fn drop(&mut self) {
-        if let Ok(mut locked_table) = LOG_CALLBACK_MAP.lock() {
-            locked_table.map.remove(&**self.context);
+        let mut count = GLOBAL_CONTEXT_REFCOUNT.lock().unwrap();
+        *count -= 1;
+
+        if *count == 0 {
+            unsafe { libusb_exit(std::ptr::null_mut()) };
         }
     }
 }
 
+impl UsbContext for GlobalContext {
+    fn as_raw(&self) -> *mut libusb_context {
+        std::ptr::null_mut()
+    }
+}
+
+/// The `poll()` readiness flags associated with a pollfd returned by
+/// [`Context::pollfds`](struct.Context.html#method.pollfds).
+#[cfg(unix)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PollEvents(c_short);
+
+#[cfg(unix)]
+impl PollEvents {
+    /// Whether the file descriptor should be polled for readability.
+    pub fn is_readable(&self) -> bool {
+        self.0 & POLLIN as c_short != 0
+    }
+
+    /// Whether the file descriptor should be polled for writability.
+    pub fn is_writable(&self) -> bool {
+        self.0 & POLLOUT as c_short != 0
+    }
+}
+
 /// Library logging levels.
 #[derive(Debug)]
 pub enum LogLevel {
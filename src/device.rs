@@ -1,22 +1,33 @@
+use libc::c_int;
 use libusb::*;
 use std::mem::MaybeUninit;
 
 use config_descriptor::{self, ConfigDescriptor};
-use context::Context;
+use context::UsbContext;
 use device_descriptor::{self, DeviceDescriptor};
 use device_handle::{self, DeviceHandle};
+use error;
 use fields::{self, Speed};
 
+/// The maximum possible depth of the USB topology; `libusb_get_port_numbers` never returns more
+/// port numbers than this.
+const MAX_PORT_NUMBERS: usize = 7;
+
 /// A reference to a USB device.
-pub struct Device {
-    context: Context,
+pub struct Device<T: UsbContext> {
+    context: T,
     device: *mut libusb_device,
+    // Whether this `Device` holds its own `libusb_ref_device` on `device`, and so must release it
+    // on drop. Devices handed out by a `DeviceList` don't (the list owns one reference for all of
+    // them, released via `libusb_free_device_list`); devices obtained some other way, e.g.
+    // `parent()` or a hotplug callback, ref the device explicitly and so must unref it themselves.
+    owns_ref: bool,
 }
 
-unsafe impl Send for Device {}
-unsafe impl Sync for Device {}
+unsafe impl<T: UsbContext> Send for Device<T> {}
+unsafe impl<T: UsbContext> Sync for Device<T> {}
 
-impl Device {
+impl<T: UsbContext> Device<T> {
     /// Reads the device descriptor.
     pub fn device_descriptor(&self) -> ::Result<DeviceDescriptor> {
         let mut descriptor: libusb_device_descriptor =
@@ -71,17 +82,73 @@ impl Device {
     }
 
     /// Opens the device.
-    pub fn open(&self) -> ::Result<DeviceHandle> {
+    pub fn open(&self) -> ::Result<DeviceHandle<T>> {
         let mut handle: *mut libusb_device_handle = unsafe { MaybeUninit::uninit().assume_init() };
         try_unsafe!(libusb_open(self.device, &mut handle));
         Ok(unsafe { device_handle::from_libusb(self.context.clone(), handle) })
     }
+
+    /// Returns the device's port number on the bus.
+    pub fn port_number(&self) -> u8 {
+        unsafe { libusb_get_port_number(self.device) }
+    }
+
+    /// Returns the full chain of port numbers from the root hub down to this device.
+    pub fn port_numbers(&self) -> ::Result<Vec<u8>> {
+        let mut ports = [0u8; MAX_PORT_NUMBERS];
+
+        let n = unsafe {
+            libusb_get_port_numbers(self.device, ports.as_mut_ptr(), ports.len() as c_int)
+        };
+
+        if n < 0 {
+            Err(error::from_libusb(n as c_int))
+        } else {
+            Ok(ports[..n as usize].to_vec())
+        }
+    }
+
+    /// Returns this device's parent in the USB topology, or `None` if it has no parent (e.g. a
+    /// root hub).
+    pub fn parent(&self) -> Option<Device<T>> {
+        let parent = unsafe { libusb_get_parent(self.device) };
+
+        if parent.is_null() {
+            None
+        } else {
+            unsafe {
+                libusb_ref_device(parent);
+                Some(from_libusb_owned(self.context.clone(), parent))
+            }
+        }
+    }
+}
+
+impl<T: UsbContext> Drop for Device<T> {
+    fn drop(&mut self) {
+        if self.owns_ref {
+            unsafe { libusb_unref_device(self.device) };
+        }
+    }
+}
+
+#[doc(hidden)]
+pub unsafe fn from_libusb<T: UsbContext>(context: T, device: *mut libusb_device) -> Device<T> {
+    Device {
+        context,
+        device,
+        owns_ref: false,
+    }
 }
 
+/// Like [`from_libusb`], but for a `device` this crate already took an explicit
+/// `libusb_ref_device` on (e.g. from `libusb_get_parent`, or a hotplug callback), making the
+/// returned `Device` responsible for releasing that reference on drop.
 #[doc(hidden)]
-pub unsafe fn from_libusb(context: Context, device: *mut libusb_device) -> Device {
+pub unsafe fn from_libusb_owned<T: UsbContext>(context: T, device: *mut libusb_device) -> Device<T> {
     Device {
         context,
-        device: device,
+        device,
+        owns_ref: true,
     }
 }
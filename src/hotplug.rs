@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+use std::os::raw::c_void;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use libc::c_int;
+use libusb::*;
+
+use context::UsbContext;
+use device;
+use error;
+
+/// The kind of hotplug event that triggered a callback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HotplugEvent {
+    /// A matching device has been plugged in.
+    DeviceArrived,
+
+    /// A matching device has been disconnected.
+    DeviceLeft,
+}
+
+impl HotplugEvent {
+    fn from_libusb(event: libusb_hotplug_event) -> Self {
+        if event == LIBUSB_HOTPLUG_EVENT_DEVICE_LEFT {
+            HotplugEvent::DeviceLeft
+        } else {
+            HotplugEvent::DeviceArrived
+        }
+    }
+}
+
+// Type-erased: the context `T` a registration was made against is captured by the closure built
+// in `register_callback` below, so the global table itself doesn't need to be generic over it.
+type ErasedHotplugCallback = Box<dyn FnMut(*mut libusb_device, HotplugEvent)>;
+
+struct HotplugCallbackMap {
+    map: HashMap<(*mut libusb_context, u64), ErasedHotplugCallback>,
+}
+
+unsafe impl Sync for HotplugCallbackMap {}
+unsafe impl Send for HotplugCallbackMap {}
+
+impl HotplugCallbackMap {
+    fn new() -> Self {
+        Self {
+            map: HashMap::new(),
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref HOTPLUG_CALLBACK_MAP: Mutex<HotplugCallbackMap> = Mutex::new(HotplugCallbackMap::new());
+}
+
+// `libusb_hotplug_callback_handle` is only known once registration returns, so each callback is
+// actually keyed by a token we hand out up front (needed to find the closure if `enumerate`
+// causes `libusb` to invoke it synchronously, before we have a handle).
+static NEXT_HOTPLUG_TOKEN: AtomicU64 = AtomicU64::new(0);
+
+extern "C" fn static_hotplug_callback(
+    _ctx: *mut libusb_context,
+    device: *mut libusb_device,
+    event: libusb_hotplug_event,
+    user_data: *mut c_void,
+) -> c_int {
+    let token = user_data as u64;
+
+    // Matched by token alone: for the default context, `libusb` passes its resolved context
+    // pointer here rather than the `NULL` the entry was inserted under.
+    if let Ok(mut locked_table) = HOTPLUG_CALLBACK_MAP.lock() {
+        let found = locked_table.map.iter_mut().find(|(key, _)| key.1 == token);
+
+        if let Some((_, callback)) = found {
+            unsafe { libusb_ref_device(device) };
+
+            callback(device, HotplugEvent::from_libusb(event));
+        }
+    }
+
+    0
+}
+
+/// Specifies which devices and events a hotplug callback should be notified about.
+///
+/// Each filter defaults to matching any value (`LIBUSB_HOTPLUG_MATCH_ANY`), and both arrival and
+/// removal events are watched unless disabled.
+pub struct HotplugBuilder {
+    vendor_id: Option<u16>,
+    product_id: Option<u16>,
+    device_class: Option<u8>,
+    enumerate: bool,
+    arrived: bool,
+    left: bool,
+}
+
+impl HotplugBuilder {
+    /// Creates a builder that matches every device and watches for both arrival and removal.
+    pub fn new() -> Self {
+        HotplugBuilder {
+            vendor_id: None,
+            product_id: None,
+            device_class: None,
+            enumerate: false,
+            arrived: true,
+            left: true,
+        }
+    }
+
+    /// Restricts the callback to devices with the given vendor ID.
+    pub fn vendor_id(mut self, vendor_id: u16) -> Self {
+        self.vendor_id = Some(vendor_id);
+        self
+    }
+
+    /// Restricts the callback to devices with the given product ID.
+    pub fn product_id(mut self, product_id: u16) -> Self {
+        self.product_id = Some(product_id);
+        self
+    }
+
+    /// Restricts the callback to devices in the given device class.
+    pub fn device_class(mut self, device_class: u8) -> Self {
+        self.device_class = Some(device_class);
+        self
+    }
+
+    /// When set, the callback also fires once for every matching device that is already plugged
+    /// in at registration time.
+    pub fn enumerate(mut self, enumerate: bool) -> Self {
+        self.enumerate = enumerate;
+        self
+    }
+
+    /// Whether the callback should be notified when a matching device arrives. Defaults to `true`.
+    pub fn arrived(mut self, arrived: bool) -> Self {
+        self.arrived = arrived;
+        self
+    }
+
+    /// Whether the callback should be notified when a matching device leaves. Defaults to `true`.
+    pub fn left(mut self, left: bool) -> Self {
+        self.left = left;
+        self
+    }
+
+    fn events(&self) -> libusb_hotplug_event {
+        let mut events = 0;
+
+        if self.arrived {
+            events |= LIBUSB_HOTPLUG_EVENT_DEVICE_ARRIVED;
+        }
+
+        if self.left {
+            events |= LIBUSB_HOTPLUG_EVENT_DEVICE_LEFT;
+        }
+
+        events
+    }
+
+    fn flags(&self) -> libusb_hotplug_flag {
+        if self.enumerate {
+            LIBUSB_HOTPLUG_ENUMERATE
+        } else {
+            0
+        }
+    }
+}
+
+impl Default for HotplugBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A guard for a registered hotplug callback.
+///
+/// Deregisters the callback with `libusb` and drops the boxed closure when dropped.
+pub struct Registration<T: UsbContext> {
+    context: T,
+    handle: libusb_hotplug_callback_handle,
+    token: u64,
+}
+
+unsafe impl<T: UsbContext> Send for Registration<T> {}
+
+impl<T: UsbContext> Drop for Registration<T> {
+    fn drop(&mut self) {
+        let ctx = self.context.as_raw();
+
+        unsafe { libusb_hotplug_deregister_callback(ctx, self.handle) };
+
+        if let Ok(mut locked_table) = HOTPLUG_CALLBACK_MAP.lock() {
+            locked_table.map.remove(&(ctx, self.token));
+        }
+    }
+}
+
+pub(crate) fn register_callback<T: UsbContext>(
+    context: &T,
+    builder: &HotplugBuilder,
+    mut callback: Box<dyn FnMut(device::Device<T>, HotplugEvent)>,
+) -> ::Result<Registration<T>> {
+    let ctx = context.as_raw();
+    let token = NEXT_HOTPLUG_TOKEN.fetch_add(1, Ordering::SeqCst);
+
+    // Erase `T` by capturing this registration's context in the closure itself; the global map
+    // then only ever stores `ErasedHotplugCallback`s, regardless of how many distinct `T`s are in
+    // use across the process.
+    let captured_context = context.clone();
+    let erased: ErasedHotplugCallback = Box::new(move |device, event| {
+        let device = unsafe { device::from_libusb_owned(captured_context.clone(), device) };
+        callback(device, event);
+    });
+
+    if let Ok(mut locked_table) = HOTPLUG_CALLBACK_MAP.lock() {
+        locked_table.map.insert((ctx, token), erased);
+    }
+
+    let mut handle: libusb_hotplug_callback_handle = 0;
+
+    let rc = unsafe {
+        libusb_hotplug_register_callback(
+            ctx,
+            builder.events(),
+            builder.flags(),
+            builder.vendor_id.map(c_int::from).unwrap_or(LIBUSB_HOTPLUG_MATCH_ANY),
+            builder.product_id.map(c_int::from).unwrap_or(LIBUSB_HOTPLUG_MATCH_ANY),
+            builder.device_class.map(c_int::from).unwrap_or(LIBUSB_HOTPLUG_MATCH_ANY),
+            static_hotplug_callback,
+            token as *mut c_void,
+            &mut handle,
+        )
+    };
+
+    if rc != 0 {
+        if let Ok(mut locked_table) = HOTPLUG_CALLBACK_MAP.lock() {
+            locked_table.map.remove(&(ctx, token));
+        }
+
+        return Err(error::from_libusb(rc));
+    }
+
+    Ok(Registration {
+        context: context.clone(),
+        handle,
+        token,
+    })
+}
@@ -0,0 +1,306 @@
+use std::os::raw::c_void;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::Duration;
+
+use libc::c_int;
+use libusb::*;
+
+use context::UsbContext;
+use device_handle::{self, DeviceHandle};
+use error;
+
+/// The outcome of a completed [`Transfer`](struct.Transfer.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferStatus {
+    /// The transfer completed without error. Note that this does not imply the full buffer was
+    /// transferred; check the length of the slice passed to the completion callback.
+    Completed,
+
+    /// The transfer failed for an unspecified reason.
+    Error,
+
+    /// The transfer timed out.
+    TimedOut,
+
+    /// The transfer was cancelled.
+    Cancelled,
+
+    /// The endpoint halted (stalled).
+    Stall,
+
+    /// The device was disconnected.
+    NoDevice,
+
+    /// The device sent more data than was requested.
+    Overflow,
+}
+
+impl TransferStatus {
+    fn from_libusb(status: libusb_transfer_status) -> Self {
+        match status {
+            LIBUSB_TRANSFER_COMPLETED => TransferStatus::Completed,
+            LIBUSB_TRANSFER_TIMED_OUT => TransferStatus::TimedOut,
+            LIBUSB_TRANSFER_CANCELLED => TransferStatus::Cancelled,
+            LIBUSB_TRANSFER_STALL => TransferStatus::Stall,
+            LIBUSB_TRANSFER_NO_DEVICE => TransferStatus::NoDevice,
+            LIBUSB_TRANSFER_OVERFLOW => TransferStatus::Overflow,
+            _ => TransferStatus::Error,
+        }
+    }
+}
+
+/// The setup packet for a control [`Transfer`](struct.Transfer.html).
+///
+/// Mirrors the fields `libusb_fill_control_setup` writes into the first 8 bytes of a control
+/// transfer's buffer.
+pub struct ControlSetup {
+    pub request_type: u8,
+    pub request: u8,
+    pub value: u16,
+    pub index: u16,
+}
+
+type TransferCallback = Box<dyn FnMut(TransferStatus, &[u8])>;
+
+// `TransferData`'s lifecycle is tracked with a single atomic state instead of independent flags,
+// so exactly one of {the completion trampoline, `Transfer::drop`} ever frees it — whichever one
+// observes the other is done touching it.
+const STATE_IDLE: u8 = 0;
+const STATE_IN_FLIGHT: u8 = 1;
+const STATE_DROP_PENDING: u8 = 2;
+
+// Owns everything the completion trampoline needs and is addressed through `libusb_transfer`'s
+// `user_data`, not a global map, since it is 1:1 with a single `libusb_transfer` and must stay at
+// a stable address across resubmission.
+struct TransferData {
+    buffer: Vec<u8>,
+    // Offset of the data stage within `buffer`; 0 for bulk/interrupt, past the 8-byte setup
+    // packet for control transfers.
+    data_offset: usize,
+    callback: TransferCallback,
+    state: AtomicU8,
+}
+
+extern "C" fn static_transfer_callback(transfer: *mut libusb_transfer) {
+    unsafe {
+        let data_ptr = (*transfer).user_data as *mut TransferData;
+        let data = &mut *data_ptr;
+
+        let status = TransferStatus::from_libusb((*transfer).status);
+        let length = (*transfer).actual_length as usize;
+        let offset = data.data_offset;
+        (data.callback)(status, &data.buffer[offset..offset + length]);
+
+        // Done touching `data`. Hand it back to idle, unless `Transfer::drop` already claimed
+        // cleanup duty while this was running, in which case we finish it off here.
+        match data.state.compare_exchange(
+            STATE_IN_FLIGHT,
+            STATE_IDLE,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        ) {
+            Ok(_) => {}
+            Err(STATE_DROP_PENDING) => {
+                libusb_free_transfer(transfer);
+                drop(Box::from_raw(data_ptr));
+            }
+            Err(_) => unreachable!("transfer completion observed from unexpected state"),
+        }
+    }
+}
+
+/// An asynchronous bulk, interrupt, or control transfer.
+///
+/// A `Transfer` owns its data buffer and the underlying `libusb_transfer`. Once [`submit`]ted, it
+/// must be driven to completion by pumping the owning [`Context`](struct.Context.html)'s events
+/// (see [`Context::handle_events`](struct.Context.html#method.handle_events)); the completion
+/// callback then runs with the transfer's status and the portion of the buffer actually
+/// transferred. A completed transfer may be [`submit`]ted again without reallocating its buffer.
+///
+/// [`submit`]: #method.submit
+pub struct Transfer {
+    transfer: *mut libusb_transfer,
+    data: *mut TransferData,
+}
+
+unsafe impl Send for Transfer {}
+
+impl Transfer {
+    fn alloc<T: UsbContext>(
+        handle: &DeviceHandle<T>,
+        buffer: Vec<u8>,
+        data_offset: usize,
+        callback: TransferCallback,
+        fill: impl FnOnce(*mut libusb_transfer, *mut libusb_device_handle, &mut [u8], *mut c_void),
+    ) -> ::Result<Self> {
+        let transfer = unsafe { libusb_alloc_transfer(0) };
+
+        if transfer.is_null() {
+            return Err(error::from_libusb(LIBUSB_ERROR_NO_MEM));
+        }
+
+        // Boxed so the buffer lives at a stable address across resubmission, regardless of where
+        // the returned `Transfer` itself is moved to.
+        let data = Box::into_raw(Box::new(TransferData {
+            buffer,
+            data_offset,
+            callback,
+            state: AtomicU8::new(STATE_IDLE),
+        }));
+
+        fill(
+            transfer,
+            device_handle::as_raw(handle),
+            unsafe { &mut (*data).buffer },
+            data as *mut c_void,
+        );
+
+        Ok(Transfer { transfer, data })
+    }
+
+    /// Creates a bulk transfer of `buffer.len()` bytes to or from `endpoint`.
+    pub fn bulk<T: UsbContext>(
+        handle: &DeviceHandle<T>,
+        endpoint: u8,
+        buffer: Vec<u8>,
+        timeout: Duration,
+        callback: TransferCallback,
+    ) -> ::Result<Self> {
+        let timeout_ms = timeout.as_millis() as std::os::raw::c_uint;
+
+        Self::alloc(handle, buffer, 0, callback, |transfer, raw_handle, buf, user_data| unsafe {
+            libusb_fill_bulk_transfer(
+                transfer,
+                raw_handle,
+                endpoint,
+                buf.as_mut_ptr(),
+                buf.len() as c_int,
+                static_transfer_callback,
+                user_data,
+                timeout_ms,
+            );
+        })
+    }
+
+    /// Creates an interrupt transfer of `buffer.len()` bytes to or from `endpoint`.
+    pub fn interrupt<T: UsbContext>(
+        handle: &DeviceHandle<T>,
+        endpoint: u8,
+        buffer: Vec<u8>,
+        timeout: Duration,
+        callback: TransferCallback,
+    ) -> ::Result<Self> {
+        let timeout_ms = timeout.as_millis() as std::os::raw::c_uint;
+
+        Self::alloc(handle, buffer, 0, callback, |transfer, raw_handle, buf, user_data| unsafe {
+            libusb_fill_interrupt_transfer(
+                transfer,
+                raw_handle,
+                endpoint,
+                buf.as_mut_ptr(),
+                buf.len() as c_int,
+                static_transfer_callback,
+                user_data,
+                timeout_ms,
+            );
+        })
+    }
+
+    /// Creates a control transfer. `data_len` is the size of the data stage; `buffer` ends up
+    /// holding the 8-byte setup packet followed by `data_len` bytes of data.
+    pub fn control<T: UsbContext>(
+        handle: &DeviceHandle<T>,
+        setup: ControlSetup,
+        data_len: usize,
+        timeout: Duration,
+        callback: TransferCallback,
+    ) -> ::Result<Self> {
+        let timeout_ms = timeout.as_millis() as std::os::raw::c_uint;
+        let setup_size = LIBUSB_CONTROL_SETUP_SIZE as usize;
+        let buffer = vec![0u8; setup_size + data_len];
+
+        Self::alloc(handle, buffer, setup_size, callback, |transfer, raw_handle, buf, user_data| unsafe {
+            libusb_fill_control_setup(
+                buf.as_mut_ptr(),
+                setup.request_type,
+                setup.request,
+                setup.value,
+                setup.index,
+                data_len as u16,
+            );
+            libusb_fill_control_transfer(
+                transfer,
+                raw_handle,
+                buf.as_mut_ptr(),
+                static_transfer_callback,
+                user_data,
+                timeout_ms,
+            );
+        })
+    }
+
+    /// Submits (or resubmits, once a previous submission has completed) this transfer.
+    ///
+    /// Returns an error without touching `libusb` if the transfer is already in flight;
+    /// resubmitting a `libusb_transfer` that hasn't completed yet is undefined behavior.
+    pub fn submit(&mut self) -> ::Result<()> {
+        let claimed = unsafe {
+            (*self.data)
+                .state
+                .compare_exchange(STATE_IDLE, STATE_IN_FLIGHT, Ordering::AcqRel, Ordering::Acquire)
+                .is_ok()
+        };
+
+        if !claimed {
+            return Err(error::from_libusb(LIBUSB_ERROR_BUSY));
+        }
+
+        let rc = unsafe { libusb_submit_transfer(self.transfer) };
+
+        if rc != 0 {
+            unsafe { (*self.data).state.store(STATE_IDLE, Ordering::Release) };
+            return Err(error::from_libusb(rc));
+        }
+
+        Ok(())
+    }
+
+    /// Requests cancellation of an in-flight transfer. The completion callback still fires, with
+    /// [`TransferStatus::Cancelled`](enum.TransferStatus.html#variant.Cancelled).
+    pub fn cancel(&mut self) -> ::Result<()> {
+        try_unsafe!(libusb_cancel_transfer(self.transfer));
+        Ok(())
+    }
+}
+
+impl Drop for Transfer {
+    fn drop(&mut self) {
+        unsafe {
+            if (*self.data).state.load(Ordering::Acquire) == STATE_IDLE {
+                libusb_free_transfer(self.transfer);
+                drop(Box::from_raw(self.data));
+                return;
+            }
+
+            libusb_cancel_transfer(self.transfer);
+
+            // Try to claim cleanup duty for when the cancellation's completion callback fires.
+            // If the trampoline already finished (and is done touching `data`) before we get
+            // here, it will have moved the state to idle itself; in that case it's safe to free
+            // directly instead of waiting for a completion that already happened.
+            match (*self.data).state.compare_exchange(
+                STATE_IN_FLIGHT,
+                STATE_DROP_PENDING,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => {}
+                Err(STATE_IDLE) => {
+                    libusb_free_transfer(self.transfer);
+                    drop(Box::from_raw(self.data));
+                }
+                Err(_) => unreachable!("transfer drop observed from unexpected state"),
+            }
+        }
+    }
+}